@@ -0,0 +1,211 @@
+//! Drives init -> deposit -> swap* through `LiteSVM` with arbitrary inputs and
+//! checks the invariants the constant-product curve is supposed to hold:
+//! vault balances only ever move by what a transfer added or a swap paid out,
+//! `k` never decreases, and a swap never pays out more than its vault holds.
+//! Overflow / "too large to price" rejections are expected outcomes, not bugs.
+
+use arbitrary::Arbitrary;
+use honggfuzz::fuzz;
+use introspectardio::test_helpers::{
+    create_mint, create_token_account, get_token_balance, mint_to, spl_token_transfer_instruction,
+    TOKEN_PROGRAM,
+};
+use introspectardio::{CURVE_CONSTANT_PRODUCT, CURVE_FIXED, MAX_FEE_BPS};
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use solana_sdk_ids::system_program;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([5; 32]);
+
+#[derive(Debug, Arbitrary)]
+struct SwapAction {
+    amount_in: u64,
+    sell_a: bool,
+}
+
+#[derive(Debug, Arbitrary)]
+struct FuzzInput {
+    usdc_atoms_per_sol: u64,
+    use_constant_product: bool,
+    fee_bps: u16,
+    deposit_amount_a: u64,
+    deposit_amount_b: u64,
+    swaps: Vec<SwapAction>,
+}
+
+fn main() {
+    loop {
+        fuzz!(|input: FuzzInput| {
+            run_one(input);
+        });
+    }
+}
+
+fn run_one(input: FuzzInput) {
+    let mut svm = LiteSVM::new()
+        .with_sigverify(false)
+        .with_blockhash_check(false)
+        .with_transaction_history(0);
+    if svm
+        .add_program_from_file(PROGRAM_ID, "target/deploy/introspectardio.so")
+        .is_err()
+    {
+        return;
+    }
+    if svm.add_program_from_file(TOKEN_PROGRAM, "ptoken.so").is_err() {
+        return;
+    }
+
+    let payer = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+
+    svm.airdrop(&payer, 100 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&user, 100 * LAMPORTS_PER_SOL).unwrap();
+
+    let (pool, _) = Pubkey::find_program_address(&[mint_a.as_ref(), mint_b.as_ref()], &PROGRAM_ID);
+    let (vault_a, _) = Pubkey::find_program_address(&[pool.as_ref(), mint_a.as_ref()], &PROGRAM_ID);
+    let (vault_b, _) = Pubkey::find_program_address(&[pool.as_ref(), mint_b.as_ref()], &PROGRAM_ID);
+    let (lp_mint, _) = Pubkey::find_program_address(&[pool.as_ref(), b"lp"], &PROGRAM_ID);
+    let (lp_lock, _) = Pubkey::find_program_address(&[pool.as_ref(), b"lp_lock"], &PROGRAM_ID);
+
+    create_mint(&mut svm, &payer, &mint_a, 9);
+    create_mint(&mut svm, &payer, &mint_b, 6);
+
+    let user_ata_a = Pubkey::new_unique();
+    let user_ata_b = Pubkey::new_unique();
+    create_token_account(&mut svm, &payer, &user_ata_a, &mint_a, &user);
+    create_token_account(&mut svm, &payer, &user_ata_b, &mint_b, &user);
+
+    // Give the user a large-but-bounded supply of both tokens so amounts drawn
+    // from the fuzzer's u64 range can't themselves overflow total supply.
+    mint_to(&mut svm, &payer, &mint_a, &user_ata_a, u64::MAX / 4);
+    mint_to(&mut svm, &payer, &mint_b, &user_ata_b, u64::MAX / 4);
+
+    let curve_kind = if input.use_constant_product {
+        CURVE_CONSTANT_PRODUCT
+    } else {
+        CURVE_FIXED
+    };
+    let fee_bps = input.fee_bps % (MAX_FEE_BPS + 1);
+    let usdc_atoms_per_sol = input.usdc_atoms_per_sol.max(1);
+
+    let mut init_data = vec![0u8];
+    init_data.extend_from_slice(&usdc_atoms_per_sol.to_le_bytes());
+    init_data.push(curve_kind);
+    init_data.extend_from_slice(&fee_bps.to_le_bytes());
+
+    let init_ixn = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new(lp_mint, false),
+            AccountMeta::new(lp_lock, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        ],
+        data: init_data,
+    };
+    let msg = Message::new(&[init_ixn], Some(&payer));
+    if svm.send_transaction(Transaction::new_unsigned(msg)).is_err() {
+        return;
+    }
+
+    // seed the pool with both reserves via the deposit path
+    let user_lp_ata = Pubkey::new_unique();
+    create_token_account(&mut svm, &payer, &user_lp_ata, &lp_mint, &user);
+
+    let deposit_amount_a = input.deposit_amount_a.max(1);
+    let deposit_amount_b = input.deposit_amount_b.max(1);
+
+    let transfer_a = spl_token_transfer_instruction(&user_ata_a, &vault_a, &user, deposit_amount_a);
+    let transfer_b = spl_token_transfer_instruction(&user_ata_b, &vault_b, &user, deposit_amount_b);
+    let deposit_ixn = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(user_lp_ata, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new(lp_mint, false),
+            AccountMeta::new(lp_lock, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        ],
+        data: vec![2],
+    };
+    let msg = Message::new(&[transfer_a, transfer_b, deposit_ixn], Some(&user));
+    if svm.send_transaction(Transaction::new_unsigned(msg)).is_err() {
+        // an unlucky (e.g. zero-LP-minted) deposit isn't a program bug on its own
+        return;
+    }
+
+    // cap how many swaps a single run drives so each fuzz iteration stays fast
+    for swap in input.swaps.iter().take(16) {
+        let (from_ata, to_vault, out_ata, out_vault) = if swap.sell_a {
+            (user_ata_a, vault_a, user_ata_b, vault_b)
+        } else {
+            (user_ata_b, vault_b, user_ata_a, vault_a)
+        };
+
+        let reserve_a_before = get_token_balance(&svm, &vault_a);
+        let reserve_b_before = get_token_balance(&svm, &vault_b);
+        let k_before = reserve_a_before as u128 * reserve_b_before as u128;
+        let out_before = get_token_balance(&svm, &out_ata);
+        let out_vault_before = get_token_balance(&svm, &out_vault);
+        let in_vault_before = get_token_balance(&svm, &to_vault);
+
+        let amount_in = swap.amount_in.max(1);
+        let transfer_ixn = spl_token_transfer_instruction(&from_ata, &to_vault, &user, amount_in);
+        let swap_ixn = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(user, true),
+                AccountMeta::new_readonly(pool, false),
+                AccountMeta::new(out_ata, false),
+                AccountMeta::new(vault_a, false),
+                AccountMeta::new(vault_b, false),
+                AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+            ],
+            data: vec![1],
+        };
+
+        let msg = Message::new(&[transfer_ixn, swap_ixn], Some(&user));
+        if svm.send_transaction(Transaction::new_unsigned(msg)).is_err() {
+            // LargeOrder / overflow / slippage-style rejections are expected paths
+            continue;
+        }
+
+        let out_after = get_token_balance(&svm, &out_ata);
+        let out_vault_after = get_token_balance(&svm, &out_vault);
+        let amount_out = out_after - out_before;
+
+        // a swap never pays out more than its output vault held
+        assert!(amount_out <= out_vault_before);
+        assert_eq!(out_vault_before - out_vault_after, amount_out);
+        // the input vault grows by exactly what the paired transfer added
+        assert_eq!(get_token_balance(&svm, &to_vault) - in_vault_before, amount_in);
+
+        if curve_kind == CURVE_CONSTANT_PRODUCT {
+            let reserve_a_after = get_token_balance(&svm, &vault_a);
+            let reserve_b_after = get_token_balance(&svm, &vault_b);
+            let k_after = reserve_a_after as u128 * reserve_b_after as u128;
+            // rounding may only ever grow k, never shrink it
+            assert!(k_after >= k_before);
+        }
+    }
+}
@@ -13,10 +13,16 @@ use pinocchio::{
     ProgramResult,
 };
 use pinocchio_system::create_account_with_minimum_balance_signed;
-use pinocchio_token::{instructions::Transfer, state::TokenAccount};
+use pinocchio_token::{
+    instructions::{Burn, MintTo, Transfer},
+    state::{Mint, TokenAccount},
+};
 
 pinocchio::entrypoint!(process);
 
+#[cfg(feature = "test-helpers")]
+pub mod test_helpers;
+
 pub const ID: Pubkey = [5; 32];
 pub const TOKEN_PROGRAM: Pubkey =
     bs58::decode_pubkey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
@@ -26,10 +32,33 @@ pub struct Pool {
     pub usdc_atoms_per_sol: U128,
     pub vault_a: Pubkey,
     pub vault_b: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_lock: Pubkey,
     // seeds: [mint_a(32), mint_b(32), bump(1)] = 65 bytes
     pub pool_seeds: [u8; 65],
+    pub curve_kind: u8,
+    pub fee_bps: u16,
 }
 
+// `Pool::curve_kind` values.
+pub const CURVE_FIXED: u8 = 0;
+pub const CURVE_CONSTANT_PRODUCT: u8 = 1;
+
+// LP tokens don't represent either underlying mint, so they get a fixed decimals
+// count rather than inheriting one from mint_a/mint_b.
+pub const LP_MINT_DECIMALS: u8 = 9;
+
+// The first deposit mints this many LP tokens to an address nobody can ever
+// withdraw against (see `lp_lock`), the same way Uniswap V2 burns it to
+// address zero: it makes a dust-deposit-then-donate inflation attack on the
+// next depositor's share unprofitable by pinning a floor under `lp_supply`.
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+// SPL token program `Transfer` instruction discriminator.
+pub const TRANSFER_DISC: u8 = 3;
+
 use uint::construct_uint;
 
 construct_uint! {
@@ -67,13 +96,16 @@ pub fn process(_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramRe
 
     match disc {
         0 => process_init(accounts, rest),
-        1 => process_swap(accounts),
+        1 => process_swap(accounts, rest),
+        2 => process_deposit(accounts),
+        3 => process_withdraw(accounts, rest),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
 
 fn process_init(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [payer, pool, vault_a, vault_b, mint_a, mint_b, _system_program, _token_program] = accounts
+    let [payer, pool, vault_a, vault_b, mint_a, mint_b, lp_mint, lp_lock, _system_program, _token_program] =
+        accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -83,6 +115,21 @@ fn process_init(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     }
 
     let usdc_atoms_per_sol = unsafe { data.as_ptr().cast::<u64>().read_unaligned() };
+    // optional 9th byte selects the pricing curve; default to the fixed-rate path
+    let curve_kind = data.get(8).copied().unwrap_or(CURVE_FIXED);
+    // the fixed-rate curve divides by this in the B->A direction; a constant-
+    // product pool never reads it, so only reject it where it's load-bearing
+    if curve_kind == CURVE_FIXED && usdc_atoms_per_sol == 0 {
+        return Err(IntrospectardioError::ZeroRate.into());
+    }
+    // optional bytes [9..11]: fee_bps (u16 LE)
+    let fee_bps = data
+        .get(9..11)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .unwrap_or(0);
+    if fee_bps > MAX_FEE_BPS {
+        return Err(IntrospectardioError::InvalidFee.into());
+    }
 
     // derive pool PDA
     let (expected_pool, bump_pool) = find_program_address(&[mint_a.key(), mint_b.key()], &ID);
@@ -101,6 +148,23 @@ fn process_init(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
         return Err(ProgramError::InvalidSeeds);
     }
 
+    // derive LP mint PDA
+    let (expected_lp_mint, bump_lp) =
+        find_program_address(&[pool.key().as_ref(), b"lp".as_ref()], &ID);
+    if !pubkey_eq(lp_mint.key(), &expected_lp_mint) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // derive the LP lock PDA: a pool-owned token account nobody can ever sign
+    // a burn/withdraw for (the pool PDA is never a transaction-level signer),
+    // so the MINIMUM_LIQUIDITY minted into it on first deposit is permanently
+    // unspendable.
+    let (expected_lp_lock, bump_lp_lock) =
+        find_program_address(&[pool.key().as_ref(), b"lp_lock".as_ref()], &ID);
+    if !pubkey_eq(lp_lock.key(), &expected_lp_lock) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
     // create pool account
     let seeds_pool = seeds!(mint_a.key(), mint_b.key(), b(&bump_pool));
     let signer_pool = Signer::from(&seeds_pool);
@@ -119,6 +183,10 @@ fn process_init(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     pool_data.usdc_atoms_per_sol = U128::from(usdc_atoms_per_sol);
     pool_data.vault_a = *vault_a.key();
     pool_data.vault_b = *vault_b.key();
+    pool_data.lp_mint = *lp_mint.key();
+    pool_data.lp_lock = *lp_lock.key();
+    pool_data.curve_kind = curve_kind;
+    pool_data.fee_bps = fee_bps;
 
     // build pool seeds: [mint_a_key, mint_b_key, bump]
     pool_data.pool_seeds[0..32].copy_from_slice(mint_a.key());
@@ -167,15 +235,66 @@ fn process_init(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     }
     .invoke()?;
 
+    let seeds_lp = seeds!(pool.key(), b"lp", b(&bump_lp));
+    let signer_lp = Signer::from(&seeds_lp);
+
+    // create LP mint
+    create_account_with_minimum_balance_signed(
+        lp_mint,
+        Mint::LEN,
+        &TOKEN_PROGRAM,
+        payer,
+        None,
+        &[signer_lp],
+    )?;
+
+    // init LP mint (pool is the mint authority)
+    pinocchio_token::instructions::InitializeMint2 {
+        mint: lp_mint,
+        decimals: LP_MINT_DECIMALS,
+        mint_authority: pool.key(),
+        freeze_authority: None,
+    }
+    .invoke()?;
+
+    let seeds_lp_lock = seeds!(pool.key(), b"lp_lock", b(&bump_lp_lock));
+    let signer_lp_lock = Signer::from(&seeds_lp_lock);
+
+    // create the LP lock account
+    create_account_with_minimum_balance_signed(
+        lp_lock,
+        TokenAccount::LEN,
+        &TOKEN_PROGRAM,
+        payer,
+        None,
+        &[signer_lp_lock],
+    )?;
+
+    // init LP lock account (pool owns it, and can never sign for it outside
+    // the CPIs this program itself issues, so nothing minted into it can ever
+    // be burned or transferred back out)
+    pinocchio_token::instructions::InitializeAccount3 {
+        account: lp_lock,
+        mint: lp_mint,
+        owner: pool.key(),
+    }
+    .invoke()?;
+
     Ok(())
 }
 
-fn process_swap(accounts: &[AccountInfo]) -> ProgramResult {
+fn process_swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     let [_payer, pool, user_out, pool_vault_a, pool_vault_b, ix_sysvar, _token_program] = accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
+    // optional 8-byte minimum_amount_out (LE u64); absent/empty payload means no floor
+    let minimum_amount_out = data
+        .get(0..8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .unwrap_or(0);
+
     let pool_data = Pool::from_account(pool)?;
 
     // verify vaults match stored keys
@@ -201,35 +320,306 @@ fn process_swap(accounts: &[AccountInfo]) -> ProgramResult {
     }
 
     let prev_ix = instruction_sysvar.load_instruction_at(prev_idx)?;
-    let amount_in = validate_prev_ix(prev_ix, pool_vault_a.clone())?;
+    let (amount_in, direction) =
+        validate_prev_ix(prev_ix, pool_vault_a.clone(), pool_vault_b.clone())?;
+
+    let (vault_in, vault_out) = match direction {
+        TradeDirection::AToB => (pool_vault_a, pool_vault_b),
+        TradeDirection::BToA => (pool_vault_b, pool_vault_a),
+    };
+
+    // Skim the trading fee off the input; the skimmed amount stays in `vault_in`
+    // rather than being priced, so it accrues to LPs instead of the trader.
+    let fee_amount = (U128::from(amount_in) * U128::from(pool_data.fee_bps) / U128::from(10_000u64))
+        .as_u64();
+    let amount_in_after_fee = amount_in - fee_amount;
 
     // Calculate amount out
-    let Some(Ok(amount_out)) = U128::from(amount_in)
-        .checked_mul(pool_data.usdc_atoms_per_sol)
-        .map(|x| x / 1_000_000_000)
-        .map(|x| x.try_into())
-    else {
-        return Err(IntrospectardioError::LargeOrder)?;
+    let is_constant_product = pool_data.curve_kind == CURVE_CONSTANT_PRODUCT;
+    let amount_out = if is_constant_product {
+        // `vault_in` already reflects the deposit that preceded this swap (the
+        // introspected transfer lands before `process_swap` runs), so its live
+        // balance already equals reserve_in + amount_in; no separate addition needed.
+        // The reserve formula is symmetric, so this works for either direction.
+        let reserve_in = vault_balance(vault_in)?;
+        if reserve_in == 0 {
+            // a live balance of zero means there's nothing to price against (and
+            // would otherwise divide by zero below); a zero-amount transfer into
+            // an empty vault is a perfectly legal way to trigger this.
+            return Err(IntrospectardioError::ZeroLiquidity.into());
+        }
+        let reserve_out = vault_balance(vault_out)?;
+        let Some(amount_out) = U128::from(reserve_out)
+            .checked_mul(U128::from(amount_in_after_fee))
+            .map(|x| x / U128::from(reserve_in))
+            .and_then(|x| x.try_into().ok())
+        else {
+            return Err(IntrospectardioError::LargeOrder)?;
+        };
+        amount_out
+    } else {
+        let rate = pool_data.usdc_atoms_per_sol;
+        let conversion = match direction {
+            // A (SOL) -> B (USDC): amount_in * rate / 1e9
+            TradeDirection::AToB => U128::from(amount_in_after_fee)
+                .checked_mul(rate)
+                .map(|x| x / 1_000_000_000),
+            // B (USDC) -> A (SOL): amount_in * 1e9 / rate
+            TradeDirection::BToA => U128::from(amount_in_after_fee)
+                .checked_mul(U128::from(1_000_000_000u64))
+                .map(|x| x / rate),
+        };
+        let Some(Ok(amount_out)) = conversion.map(|x| x.try_into()) else {
+            return Err(IntrospectardioError::LargeOrder)?;
+        };
+        amount_out
     };
 
-    // Transfer out (pool signs for vault_b)
+    if amount_out < minimum_amount_out {
+        return Err(IntrospectardioError::SlippageExceeded.into());
+    }
+
+    // Transfer out (pool signs for the output vault)
     let mint_a_key = &pool_data.pool_seeds[0..32];
     let mint_b_key = &pool_data.pool_seeds[32..64];
     let bump = &pool_data.pool_seeds[64..65];
     let seeds = seeds!(mint_a_key, mint_b_key, bump);
     let signer = Signer::from(&seeds);
 
+    let invariant_before = if is_constant_product {
+        let reserve_in_before = vault_balance(vault_in)?.saturating_sub(amount_in);
+        let reserve_out_before = vault_balance(vault_out)?;
+        Some(U128::from(reserve_in_before) * U128::from(reserve_out_before))
+    } else {
+        None
+    };
+
     Transfer {
-        from: pool_vault_b,
+        from: vault_out,
         to: user_out,
         authority: pool,
         amount: amount_out,
     }
     .invoke_signed(&[signer])?;
 
+    if let Some(invariant_before) = invariant_before {
+        let reserve_in_after = vault_balance(vault_in)?;
+        let reserve_out_after = vault_balance(vault_out)?;
+        let invariant_after = U128::from(reserve_in_after) * U128::from(reserve_out_after);
+        if invariant_after < invariant_before {
+            return Err(IntrospectardioError::InvariantViolated.into());
+        }
+    }
+
+    Ok(())
+}
+
+fn process_deposit(accounts: &[AccountInfo]) -> ProgramResult {
+    let [_payer, pool, user_lp_account, pool_vault_a, pool_vault_b, lp_mint, lp_lock, ix_sysvar, _token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let pool_data = Pool::from_account(pool)?;
+
+    // verify vaults and LP mint match stored keys
+    if !pubkey_eq(pool_vault_a.key(), &pool_data.vault_a) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !pubkey_eq(pool_vault_b.key(), &pool_data.vault_b) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !pubkey_eq(lp_mint.key(), &pool_data.lp_mint) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !pubkey_eq(lp_lock.key(), &pool_data.lp_lock) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let instruction_sysvar = unsafe { Instructions::new_unchecked(ix_sysvar.try_borrow_data()?) };
+    let cur_idx = instruction_sysvar.load_current_index() as usize;
+    if cur_idx < 2 {
+        return Err(IntrospectardioError::PrevIxNotTokenProgram.into());
+    }
+
+    let curr_ixn =
+        unsafe { instruction_sysvar.deserialize_instruction_unchecked(cur_idx as usize) };
+    if *curr_ixn.get_program_id() != ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // the two instructions immediately preceding this one must be the matching
+    // A and B transfers into the pool's vaults
+    let first_ix = instruction_sysvar.load_instruction_at(cur_idx - 2)?;
+    let second_ix = instruction_sysvar.load_instruction_at(cur_idx - 1)?;
+    let (amount_first, dir_first) =
+        validate_prev_ix(first_ix, pool_vault_a.clone(), pool_vault_b.clone())?;
+    let (amount_second, dir_second) =
+        validate_prev_ix(second_ix, pool_vault_a.clone(), pool_vault_b.clone())?;
+
+    let (amount_a, amount_b) = match (dir_first, dir_second) {
+        (TradeDirection::AToB, TradeDirection::BToA) => (amount_first, amount_second),
+        (TradeDirection::BToA, TradeDirection::AToB) => (amount_second, amount_first),
+        _ => return Err(IntrospectardioError::UnexpectedTransferDest.into()),
+    };
+
+    // reserves as they stood before this deposit's transfers landed
+    let reserve_a = vault_balance(pool_vault_a)?.saturating_sub(amount_a);
+    let reserve_b = vault_balance(pool_vault_b)?.saturating_sub(amount_b);
+    let lp_supply = mint_supply(lp_mint)?;
+
+    // On the very first deposit, permanently lock away MINIMUM_LIQUIDITY LP
+    // tokens so `lp_supply` can never be pushed back down to (or started at) a
+    // dust amount: without this, someone could front-run pool creation with a
+    // dust deposit, donate a large amount straight into the vaults without
+    // minting LP for it, and leave the next real depositor's share rounded
+    // down to near-nothing by `from_a`/`from_b` below.
+    let (lp_amount, lock_amount) = if lp_supply == 0 {
+        let minted = integer_sqrt(U128::from(amount_a) * U128::from(amount_b)).as_u64();
+        let Some(user_amount) = minted.checked_sub(MINIMUM_LIQUIDITY) else {
+            return Err(IntrospectardioError::ZeroLiquidity.into());
+        };
+        (user_amount, MINIMUM_LIQUIDITY)
+    } else if reserve_a == 0 || reserve_b == 0 {
+        return Err(IntrospectardioError::ZeroLiquidity.into());
+    } else {
+        let from_a = U128::from(amount_a) * U128::from(lp_supply) / U128::from(reserve_a);
+        let from_b = U128::from(amount_b) * U128::from(lp_supply) / U128::from(reserve_b);
+        (from_a.min(from_b).as_u64(), 0)
+    };
+
+    if lp_amount == 0 {
+        return Err(IntrospectardioError::ZeroLiquidity.into());
+    }
+
+    let mint_a_key = &pool_data.pool_seeds[0..32];
+    let mint_b_key = &pool_data.pool_seeds[32..64];
+    let bump = &pool_data.pool_seeds[64..65];
+    let seeds = seeds!(mint_a_key, mint_b_key, bump);
+
+    if lock_amount > 0 {
+        MintTo {
+            mint: lp_mint,
+            account: lp_lock,
+            mint_authority: pool,
+            amount: lock_amount,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+    }
+
+    MintTo {
+        mint: lp_mint,
+        account: user_lp_account,
+        mint_authority: pool,
+        amount: lp_amount,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Ok(())
+}
+
+fn process_withdraw(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, pool, user_lp_account, user_out_a, user_out_b, pool_vault_a, pool_vault_b, lp_mint, _token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if data.len() < 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let lp_amount = unsafe { data.as_ptr().cast::<u64>().read_unaligned() };
+
+    let pool_data = Pool::from_account(pool)?;
+
+    if !pubkey_eq(pool_vault_a.key(), &pool_data.vault_a) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !pubkey_eq(pool_vault_b.key(), &pool_data.vault_b) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !pubkey_eq(lp_mint.key(), &pool_data.lp_mint) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let lp_supply = mint_supply(lp_mint)?;
+    if lp_supply == 0 {
+        return Err(IntrospectardioError::ZeroLiquidity.into());
+    }
+
+    let reserve_a = vault_balance(pool_vault_a)?;
+    let reserve_b = vault_balance(pool_vault_b)?;
+
+    let amount_a = (U128::from(lp_amount) * U128::from(reserve_a) / U128::from(lp_supply)).as_u64();
+    let amount_b = (U128::from(lp_amount) * U128::from(reserve_b) / U128::from(lp_supply)).as_u64();
+
+    Burn {
+        mint: lp_mint,
+        account: user_lp_account,
+        authority: user,
+        amount: lp_amount,
+    }
+    .invoke()?;
+
+    let mint_a_key = &pool_data.pool_seeds[0..32];
+    let mint_b_key = &pool_data.pool_seeds[32..64];
+    let bump = &pool_data.pool_seeds[64..65];
+    let seeds = seeds!(mint_a_key, mint_b_key, bump);
+
+    Transfer {
+        from: pool_vault_a,
+        to: user_out_a,
+        authority: pool,
+        amount: amount_a,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
+    Transfer {
+        from: pool_vault_b,
+        to: user_out_b,
+        authority: pool,
+        amount: amount_b,
+    }
+    .invoke_signed(&[Signer::from(&seeds)])?;
+
     Ok(())
 }
 
+// Reads the live `amount` field (offset 64) out of an SPL `TokenAccount` without
+// requiring the fully-typed account layout, mirroring the raw read used for fixed-rate
+// pricing elsewhere in this program.
+fn vault_balance(account: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = account.try_borrow_data()?;
+    if data.len() < TokenAccount::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(unsafe { data.as_ptr().add(64).cast::<u64>().read_unaligned() })
+}
+
+// Reads the live `supply` field (offset 36) out of an SPL `Mint`.
+fn mint_supply(account: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = account.try_borrow_data()?;
+    if data.len() < Mint::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(unsafe { data.as_ptr().add(36).cast::<u64>().read_unaligned() })
+}
+
+// Babylonian method integer square root, as used by Uniswap V2's `Math.sqrt`.
+fn integer_sqrt(value: U128) -> U128 {
+    if value.is_zero() {
+        return U128::zero();
+    }
+    let mut x = value;
+    let mut y = (x + U128::from(1u64)) / U128::from(2u64);
+    while y < x {
+        x = y;
+        y = (x + value / x) / U128::from(2u64);
+    }
+    x
+}
+
 #[repr(u32)]
 pub enum IntrospectardioError {
     PrevIxNotTokenProgram,
@@ -237,6 +627,11 @@ pub enum IntrospectardioError {
     UnexpectedTokenProgramIx,
     UnexpectedTransferDest,
     LargeOrder,
+    InvariantViolated,
+    ZeroLiquidity,
+    InvalidFee,
+    SlippageExceeded,
+    ZeroRate,
 }
 
 impl From<IntrospectardioError> for ProgramError {
@@ -245,17 +640,26 @@ impl From<IntrospectardioError> for ProgramError {
     }
 }
 
+// Which side of the pool an introspected deposit landed in, mirroring the
+// `TradeDirection` concept from SPL token-swap.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TradeDirection {
+    AToB,
+    BToA,
+}
+
 // Previous instruction must be
 // 1) token program invocation
 // 2) transfer ix data len
 // 3) transfer ix disc
-// 4) transfer dest is pool vault
+// 4) transfer dest is one of the pool's vaults
 //
 // If we are executing this code, it's because the instruction succeeded!
 fn validate_prev_ix(
     prev_ix: IntrospectedInstruction,
-    pool_vault_in: AccountInfo,
-) -> Result<u64, ProgramError> {
+    pool_vault_a: AccountInfo,
+    pool_vault_b: AccountInfo,
+) -> Result<(u64, TradeDirection), ProgramError> {
     // 1) token program invocation
     if !pubkey_eq(prev_ix.get_program_id(), &TOKEN_PROGRAM) {
         return Err(IntrospectardioError::PrevIxNotTokenProgram.into());
@@ -269,21 +673,23 @@ fn validate_prev_ix(
     }
 
     // 3) transfer ix disc
-    const TRANSFER_DISC: u8 = 3;
     let correct_disc = prev_ix_data[0] == TRANSFER_DISC;
     if !correct_disc {
         return Err(IntrospectardioError::UnexpectedTokenProgramIx.into());
     }
 
-    // 4) transfer dest is pool vault
+    // 4) transfer dest is either pool vault
     // SAFETY: transfer succeeded so num accounts is correct
     let dest = unsafe { prev_ix.get_account_meta_at_unchecked(1) };
-    let correct_dest = dest.key.eq(pool_vault_in.key());
-    if !correct_dest {
+    let direction = if dest.key.eq(pool_vault_a.key()) {
+        TradeDirection::AToB
+    } else if dest.key.eq(pool_vault_b.key()) {
+        TradeDirection::BToA
+    } else {
         return Err(IntrospectardioError::UnexpectedTransferDest.into());
-    }
+    };
 
     // read amount in
     let amount_in = unsafe { prev_ix_data.as_ptr().add(1).cast::<u64>().read_unaligned() };
-    Ok(amount_in)
+    Ok((amount_in, direction))
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+//! `LiteSVM`-backed setup helpers shared by the integration test and the
+//! fuzz harness, so both drive the program through one code path. Gated
+//! behind the `test-helpers` feature so ordinary builds don't pull in
+//! `litesvm`/`solana-sdk`.
+
+use litesvm::LiteSVM;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+pub const TOKEN_PROGRAM: Pubkey = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+pub fn create_mint(svm: &mut LiteSVM, payer: &Pubkey, mint: &Pubkey, decimals: u8) {
+    let mint_space = 82; // Mint::LEN
+    let rent = svm.minimum_balance_for_rent_exemption(mint_space);
+    let create_ixn = solana_system_interface::instruction::create_account(
+        payer,
+        mint,
+        rent,
+        mint_space as u64,
+        &TOKEN_PROGRAM,
+    );
+
+    // InitializeMint2: disc=20, decimals, mint_authority, freeze_authority_option=0
+    let mut init_data = vec![20u8, decimals];
+    init_data.extend_from_slice(payer.as_ref()); // mint authority
+    init_data.push(0); // no freeze authority
+
+    let init_ixn = Instruction {
+        program_id: TOKEN_PROGRAM,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data: init_data,
+    };
+
+    let msg = Message::new(&[create_ixn, init_ixn], Some(payer));
+    let txn = Transaction::new_unsigned(msg);
+    svm.send_transaction(txn).unwrap();
+}
+
+pub fn create_token_account(
+    svm: &mut LiteSVM,
+    payer: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) {
+    let account_space = 165; // TokenAccount::LEN
+    let rent = svm.minimum_balance_for_rent_exemption(account_space);
+    let create_ixn = solana_system_interface::instruction::create_account(
+        payer,
+        account,
+        rent,
+        account_space as u64,
+        &TOKEN_PROGRAM,
+    );
+
+    // InitializeAccount3: disc=18, owner
+    let mut init_data = vec![18u8];
+    init_data.extend_from_slice(owner.as_ref());
+
+    let init_ixn = Instruction {
+        program_id: TOKEN_PROGRAM,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*mint, false),
+        ],
+        data: init_data,
+    };
+
+    let msg = Message::new(&[create_ixn, init_ixn], Some(payer));
+    let txn = Transaction::new_unsigned(msg);
+    svm.send_transaction(txn).unwrap();
+}
+
+pub fn mint_to(svm: &mut LiteSVM, authority: &Pubkey, mint: &Pubkey, dest: &Pubkey, amount: u64) {
+    // MintTo: disc=7, amount
+    let mut data = vec![7u8];
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let ixn = Instruction {
+        program_id: TOKEN_PROGRAM,
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*dest, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    };
+
+    let msg = Message::new(&[ixn], Some(authority));
+    let txn = Transaction::new_unsigned(msg);
+    svm.send_transaction(txn).unwrap();
+}
+
+pub fn spl_token_transfer_instruction(
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    // Transfer: disc=3, amount
+    let mut data = vec![3u8];
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    Instruction {
+        program_id: TOKEN_PROGRAM,
+        accounts: vec![
+            AccountMeta::new(*from, false),
+            AccountMeta::new(*to, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+pub fn get_token_balance(svm: &LiteSVM, account: &Pubkey) -> u64 {
+    let acc = svm.get_account(account).unwrap();
+    // amount is at offset 64 in TokenAccount
+    unsafe { acc.data.as_ptr().add(64).cast::<u64>().read_unaligned() }
+}
+
+pub fn get_mint_supply(svm: &LiteSVM, mint: &Pubkey) -> u64 {
+    let acc = svm.get_account(mint).unwrap();
+    // supply is at offset 36 in Mint
+    unsafe { acc.data.as_ptr().add(36).cast::<u64>().read_unaligned() }
+}
+
+// Babylonian method integer square root, mirroring the program's `integer_sqrt`.
+pub fn integer_sqrt(value: u128) -> u128 {
+    if value == 0 {
+        return 0;
+    }
+    let mut x = value;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
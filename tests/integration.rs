@@ -1,3 +1,8 @@
+use introspectardio::test_helpers::{
+    create_mint, create_token_account, get_mint_supply, get_token_balance, integer_sqrt, mint_to,
+    spl_token_transfer_instruction, TOKEN_PROGRAM,
+};
+use introspectardio::MINIMUM_LIQUIDITY;
 use litesvm::LiteSVM;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
@@ -7,10 +12,8 @@ use solana_sdk::{
     transaction::Transaction,
 };
 use solana_sdk_ids::system_program;
-use solana_system_interface::instruction::create_account;
 
 const PROGRAM_ID: Pubkey = Pubkey::new_from_array([5; 32]);
-const TOKEN_PROGRAM: Pubkey = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
 #[test]
 fn integration() {
@@ -36,6 +39,8 @@ fn integration() {
     let (pool, _) = Pubkey::find_program_address(&[mint_a.as_ref(), mint_b.as_ref()], &PROGRAM_ID);
     let (vault_a, _) = Pubkey::find_program_address(&[pool.as_ref(), mint_a.as_ref()], &PROGRAM_ID);
     let (vault_b, _) = Pubkey::find_program_address(&[pool.as_ref(), mint_b.as_ref()], &PROGRAM_ID);
+    let (lp_mint, _) = Pubkey::find_program_address(&[pool.as_ref(), b"lp"], &PROGRAM_ID);
+    let (lp_lock, _) = Pubkey::find_program_address(&[pool.as_ref(), b"lp_lock"], &PROGRAM_ID);
 
 
     // create mints
@@ -71,6 +76,8 @@ fn integration() {
             AccountMeta::new(vault_b, false),
             AccountMeta::new_readonly(mint_a, false),
             AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new(lp_mint, false),
+            AccountMeta::new(lp_lock, false),
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(TOKEN_PROGRAM, false),
         ],
@@ -131,101 +138,455 @@ fn integration() {
         "Swap successful: {} SOL -> {} USDC atoms",
         amount_in, expected_out
     );
-}
 
-fn create_mint(svm: &mut LiteSVM, payer: &Pubkey, mint: &Pubkey, decimals: u8) {
-    let mint_space = 82; // Mint::LEN
-    let rent = svm.minimum_balance_for_rent_exemption(mint_space);
-    let create_ixn = create_account(payer, mint, rent, mint_space as u64, &TOKEN_PROGRAM);
+    // 3) Deposit liquidity
+    let user_lp_ata = Pubkey::new_unique();
+    create_token_account(&mut svm, &payer, &user_lp_ata, &lp_mint, &user);
 
-    // InitializeMint2: disc=20, decimals, mint_authority, freeze_authority_option=0
-    let mut init_data = vec![20u8, decimals];
-    init_data.extend_from_slice(payer.as_ref()); // mint authority
-    init_data.push(0); // no freeze authority
+    let deposit_amount_a: u64 = LAMPORTS_PER_SOL; // 1 SOL
+    let deposit_amount_b: u64 = 500 * 1_000_000; // 500 USDC
 
-    let init_ixn = Instruction {
-        program_id: TOKEN_PROGRAM,
-        accounts: vec![AccountMeta::new(*mint, false)],
-        data: init_data,
+    let deposit_transfer_a =
+        spl_token_transfer_instruction(&user_ata_a, &vault_a, &user, deposit_amount_a);
+    let deposit_transfer_b =
+        spl_token_transfer_instruction(&user_ata_b, &vault_b, &user, deposit_amount_b);
+
+    let deposit_ixn = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(user_lp_ata, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new(lp_mint, false),
+            AccountMeta::new(lp_lock, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
+        ],
+        data: vec![2], // deposit discriminator
     };
 
-    let msg = Message::new(&[create_ixn, init_ixn], Some(payer));
+    let reserve_a_before_deposit = get_token_balance(&svm, &vault_a);
+    let reserve_b_before_deposit = get_token_balance(&svm, &vault_b);
+
+    let msg = Message::new(
+        &[deposit_transfer_a, deposit_transfer_b, deposit_ixn],
+        Some(&user),
+    );
     let txn = Transaction::new_unsigned(msg);
-    svm.send_transaction(txn).unwrap();
-}
+    let res = svm.send_transaction(txn).unwrap();
 
-fn create_token_account(
-    svm: &mut LiteSVM,
-    payer: &Pubkey,
-    account: &Pubkey,
-    mint: &Pubkey,
-    owner: &Pubkey,
-) {
-    let account_space = 165; // TokenAccount::LEN
-    let rent = svm.minimum_balance_for_rent_exemption(account_space);
-    let create_ixn = create_account(payer, account, rent, account_space as u64, &TOKEN_PROGRAM);
-
-    // InitializeAccount3: disc=18, owner
-    let mut init_data = vec![18u8];
-    init_data.extend_from_slice(owner.as_ref());
+    println!("Deposit");
+    for log in res.logs {
+        println!("    {log}");
+    }
 
-    let init_ixn = Instruction {
-        program_id: TOKEN_PROGRAM,
+    let lp_balance = get_token_balance(&svm, &user_lp_ata);
+    let total_lp_minted = integer_sqrt(deposit_amount_a as u128 * deposit_amount_b as u128) as u64;
+    let expected_lp = total_lp_minted - MINIMUM_LIQUIDITY;
+    assert_eq!(lp_balance, expected_lp);
+    assert_eq!(get_token_balance(&svm, &lp_lock), MINIMUM_LIQUIDITY);
+
+    let reserve_a_after_deposit = get_token_balance(&svm, &vault_a);
+    let reserve_b_after_deposit = get_token_balance(&svm, &vault_b);
+    assert_eq!(
+        reserve_a_after_deposit,
+        reserve_a_before_deposit + deposit_amount_a
+    );
+    assert_eq!(
+        reserve_b_after_deposit,
+        reserve_b_before_deposit + deposit_amount_b
+    );
+
+    println!("Deposit successful: minted {} LP tokens", lp_balance);
+
+    // 4) Withdraw half the LP position
+    let withdraw_lp: u64 = lp_balance / 2;
+    let lp_supply_before_withdraw = get_mint_supply(&svm, &lp_mint);
+
+    let expected_amount_a_out =
+        (withdraw_lp as u128 * reserve_a_after_deposit as u128 / lp_supply_before_withdraw as u128)
+            as u64;
+    let expected_amount_b_out =
+        (withdraw_lp as u128 * reserve_b_after_deposit as u128 / lp_supply_before_withdraw as u128)
+            as u64;
+
+    let mut withdraw_data = vec![3u8]; // withdraw discriminator
+    withdraw_data.extend_from_slice(&withdraw_lp.to_le_bytes());
+
+    let withdraw_ixn = Instruction {
+        program_id: PROGRAM_ID,
         accounts: vec![
-            AccountMeta::new(*account, false),
-            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(user, true),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(user_lp_ata, false),
+            AccountMeta::new(user_ata_a, false),
+            AccountMeta::new(user_ata_b, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new(lp_mint, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
         ],
-        data: init_data,
+        data: withdraw_data,
     };
 
-    let msg = Message::new(&[create_ixn, init_ixn], Some(payer));
+    let before_user_a = get_token_balance(&svm, &user_ata_a);
+    let before_user_b = get_token_balance(&svm, &user_ata_b);
+
+    let msg = Message::new(&[withdraw_ixn], Some(&user));
     let txn = Transaction::new_unsigned(msg);
-    svm.send_transaction(txn).unwrap();
+    let res = svm.send_transaction(txn).unwrap();
+
+    println!("Withdraw");
+    for log in res.logs {
+        println!("    {log}");
+    }
+
+    assert_eq!(
+        get_token_balance(&svm, &user_ata_a) - before_user_a,
+        expected_amount_a_out
+    );
+    assert_eq!(
+        get_token_balance(&svm, &user_ata_b) - before_user_b,
+        expected_amount_b_out
+    );
+    assert_eq!(get_token_balance(&svm, &user_lp_ata), lp_balance - withdraw_lp);
+    assert_eq!(
+        get_mint_supply(&svm, &lp_mint),
+        lp_supply_before_withdraw - withdraw_lp
+    );
+
+    println!(
+        "Withdraw successful: burned {} LP tokens for {} SOL, {} USDC atoms",
+        withdraw_lp, expected_amount_a_out, expected_amount_b_out
+    );
+}
+
+// Shared scaffolding for the single-path tests below: spins up a fresh pool
+// (SOL-wrapped mint_a @ 9 decimals, USDC-like mint_b @ 6 decimals) and funds
+// the user with both, without touching liquidity/reserves.
+struct PoolSetup {
+    svm: LiteSVM,
+    user: Pubkey,
+    pool: Pubkey,
+    vault_a: Pubkey,
+    vault_b: Pubkey,
+    user_ata_a: Pubkey,
+    user_ata_b: Pubkey,
 }
 
-fn mint_to(svm: &mut LiteSVM, authority: &Pubkey, mint: &Pubkey, dest: &Pubkey, amount: u64) {
-    // MintTo: disc=7, amount
-    let mut data = vec![7u8];
-    data.extend_from_slice(&amount.to_le_bytes());
+fn setup_pool(curve_kind: u8, fee_bps: u16, usdc_atoms_per_sol: u64) -> PoolSetup {
+    try_setup_pool(curve_kind, fee_bps, usdc_atoms_per_sol).unwrap()
+}
+
+// Like `setup_pool`, but surfaces a failed `init` instead of unwrapping it, for
+// tests that exercise `process_init`'s own validation.
+fn try_setup_pool(
+    curve_kind: u8,
+    fee_bps: u16,
+    usdc_atoms_per_sol: u64,
+) -> Result<PoolSetup, litesvm::types::FailedTransactionMetadata> {
+    let mut svm = LiteSVM::new()
+        .with_sigverify(false)
+        .with_blockhash_check(false)
+        .with_transaction_history(0);
+    svm.add_program_from_file(PROGRAM_ID, "target/deploy/introspectardio.so")
+        .unwrap();
+    svm.add_program_from_file(TOKEN_PROGRAM, "ptoken.so")
+        .unwrap();
+
+    let payer = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let mint_a = Pubkey::new_unique();
+    let mint_b = Pubkey::new_unique();
+
+    svm.airdrop(&payer, 100 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&user, 100 * LAMPORTS_PER_SOL).unwrap();
+
+    let (pool, _) = Pubkey::find_program_address(&[mint_a.as_ref(), mint_b.as_ref()], &PROGRAM_ID);
+    let (vault_a, _) = Pubkey::find_program_address(&[pool.as_ref(), mint_a.as_ref()], &PROGRAM_ID);
+    let (vault_b, _) = Pubkey::find_program_address(&[pool.as_ref(), mint_b.as_ref()], &PROGRAM_ID);
+    let (lp_mint, _) = Pubkey::find_program_address(&[pool.as_ref(), b"lp"], &PROGRAM_ID);
+    let (lp_lock, _) = Pubkey::find_program_address(&[pool.as_ref(), b"lp_lock"], &PROGRAM_ID);
+
+    create_mint(&mut svm, &payer, &mint_a, 9);
+    create_mint(&mut svm, &payer, &mint_b, 6);
+
+    let user_ata_a = Pubkey::new_unique();
+    let user_ata_b = Pubkey::new_unique();
+    create_token_account(&mut svm, &payer, &user_ata_a, &mint_a, &user);
+    create_token_account(&mut svm, &payer, &user_ata_b, &mint_b, &user);
+
+    mint_to(&mut svm, &payer, &mint_a, &user_ata_a, 100 * LAMPORTS_PER_SOL);
+    mint_to(&mut svm, &payer, &mint_b, &user_ata_b, 100_000 * 1_000_000);
 
-    let ixn = Instruction {
-        program_id: TOKEN_PROGRAM,
+    let mut init_data = vec![0u8];
+    init_data.extend_from_slice(&usdc_atoms_per_sol.to_le_bytes());
+    init_data.push(curve_kind);
+    init_data.extend_from_slice(&fee_bps.to_le_bytes());
+
+    let init_ixn = Instruction {
+        program_id: PROGRAM_ID,
         accounts: vec![
-            AccountMeta::new(*mint, false),
-            AccountMeta::new(*dest, false),
-            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new(lp_mint, false),
+            AccountMeta::new(lp_lock, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
         ],
-        data,
+        data: init_data,
     };
-
-    let msg = Message::new(&[ixn], Some(authority));
-    let txn = Transaction::new_unsigned(msg);
-    svm.send_transaction(txn).unwrap();
+    let msg = Message::new(&[init_ixn], Some(&payer));
+    svm.send_transaction(Transaction::new_unsigned(msg))?;
+
+    Ok(PoolSetup {
+        svm,
+        user,
+        pool,
+        vault_a,
+        vault_b,
+        user_ata_a,
+        user_ata_b,
+    })
 }
 
-fn spl_token_transfer_instruction(
-    from: &Pubkey,
-    to: &Pubkey,
-    authority: &Pubkey,
-    amount: u64,
-) -> Instruction {
-    // Transfer: disc=3, amount
-    let mut data = vec![3u8];
-    data.extend_from_slice(&amount.to_le_bytes());
-
+fn swap_ixn(setup: &PoolSetup, user_out: Pubkey, data: Vec<u8>) -> Instruction {
     Instruction {
-        program_id: TOKEN_PROGRAM,
+        program_id: PROGRAM_ID,
         accounts: vec![
-            AccountMeta::new(*from, false),
-            AccountMeta::new(*to, false),
-            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(setup.user, true),
+            AccountMeta::new_readonly(setup.pool, false),
+            AccountMeta::new(user_out, false),
+            AccountMeta::new(setup.vault_a, false),
+            AccountMeta::new(setup.vault_b, false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM, false),
         ],
         data,
     }
 }
 
-fn get_token_balance(svm: &LiteSVM, account: &Pubkey) -> u64 {
-    let acc = svm.get_account(account).unwrap();
-    // amount is at offset 64 in TokenAccount
-    unsafe { acc.data.as_ptr().add(64).cast::<u64>().read_unaligned() }
+#[test]
+fn constant_product_swap_matches_reserve_formula() {
+    let mut setup = setup_pool(introspectardio::CURVE_CONSTANT_PRODUCT, 0, 0);
+
+    // fund both vaults directly to stand in for an initial liquidity position
+    let reserve_a: u64 = 10 * LAMPORTS_PER_SOL;
+    let reserve_b: u64 = 10_000 * 1_000_000;
+
+    // seed reserves with a plain transfer (equivalent to funds already on deposit)
+    let seed_a = spl_token_transfer_instruction(&setup.user_ata_a, &setup.vault_a, &setup.user, reserve_a);
+    let seed_b = spl_token_transfer_instruction(&setup.user_ata_b, &setup.vault_b, &setup.user, reserve_b);
+    let msg = Message::new(&[seed_a, seed_b], Some(&setup.user));
+    setup.svm.send_transaction(Transaction::new_unsigned(msg)).unwrap();
+
+    let amount_in: u64 = LAMPORTS_PER_SOL; // 1 SOL
+    let transfer_ixn =
+        spl_token_transfer_instruction(&setup.user_ata_a, &setup.vault_a, &setup.user, amount_in);
+    let swap = swap_ixn(&setup, setup.user_ata_b, vec![1]);
+
+    let before = get_token_balance(&setup.svm, &setup.user_ata_b);
+    let msg = Message::new(&[transfer_ixn, swap], Some(&setup.user));
+    setup.svm.send_transaction(Transaction::new_unsigned(msg)).unwrap();
+    let after = get_token_balance(&setup.svm, &setup.user_ata_b);
+
+    // amount_out = reserve_out * amount_in / (reserve_in + amount_in), the
+    // live vault_a balance already including this swap's own deposit
+    let expected_out =
+        (reserve_b as u128 * amount_in as u128 / (reserve_a as u128 + amount_in as u128)) as u64;
+    assert_eq!(after - before, expected_out);
+}
+
+#[test]
+fn swap_b_to_a_fixed_rate() {
+    let usdc_atoms_per_sol: u64 = 1_000 * 1_000_000; // $1000 per SOL
+    let mut setup = setup_pool(introspectardio::CURVE_FIXED, 0, usdc_atoms_per_sol);
+
+    // give vault_a enough SOL-wrapped tokens to pay out the B->A swap
+    let seed_a = spl_token_transfer_instruction(
+        &setup.user_ata_a,
+        &setup.vault_a,
+        &setup.user,
+        10 * LAMPORTS_PER_SOL,
+    );
+    setup
+        .svm
+        .send_transaction(Transaction::new_unsigned(Message::new(
+            &[seed_a],
+            Some(&setup.user),
+        )))
+        .unwrap();
+
+    // user transfers USDC into vault_b, then calls swap; the direction
+    // (B->A) is inferred from the transfer's destination
+    let amount_in: u64 = 500 * 1_000_000; // 500 USDC
+    let transfer_ixn =
+        spl_token_transfer_instruction(&setup.user_ata_b, &setup.vault_b, &setup.user, amount_in);
+    let swap = swap_ixn(&setup, setup.user_ata_a, vec![1]);
+
+    let before = get_token_balance(&setup.svm, &setup.user_ata_a);
+    let msg = Message::new(&[transfer_ixn, swap], Some(&setup.user));
+    setup.svm.send_transaction(Transaction::new_unsigned(msg)).unwrap();
+    let after = get_token_balance(&setup.svm, &setup.user_ata_a);
+
+    // B (USDC) -> A (SOL): amount_in * 1e9 / rate
+    let expected_out = amount_in * LAMPORTS_PER_SOL / usdc_atoms_per_sol;
+    assert_eq!(after - before, expected_out);
+}
+
+#[test]
+fn init_rejects_zero_rate_on_fixed_curve() {
+    // a zero rate is only ever divided by in the B->A branch of the
+    // fixed-rate curve; `process_init` must reject it up front rather than
+    // let a later swap hit a `U128` divide-by-zero
+    assert!(try_setup_pool(introspectardio::CURVE_FIXED, 0, 0).is_err());
+}
+
+#[test]
+fn swap_skims_configured_fee() {
+    let usdc_atoms_per_sol: u64 = 1_000 * 1_000_000; // $1000 per SOL
+    let fee_bps: u16 = 100; // 1%
+    let mut setup = setup_pool(introspectardio::CURVE_FIXED, fee_bps, usdc_atoms_per_sol);
+
+    // give vault_b enough USDC to pay out the A->B swap
+    let seed_b = spl_token_transfer_instruction(
+        &setup.user_ata_b,
+        &setup.vault_b,
+        &setup.user,
+        10_000 * 1_000_000,
+    );
+    setup
+        .svm
+        .send_transaction(Transaction::new_unsigned(Message::new(
+            &[seed_b],
+            Some(&setup.user),
+        )))
+        .unwrap();
+
+    let amount_in: u64 = LAMPORTS_PER_SOL; // 1 SOL
+    let transfer_ixn =
+        spl_token_transfer_instruction(&setup.user_ata_a, &setup.vault_a, &setup.user, amount_in);
+    let swap = swap_ixn(&setup, setup.user_ata_b, vec![1]);
+
+    let before_out = get_token_balance(&setup.svm, &setup.user_ata_b);
+    let vault_a_before = get_token_balance(&setup.svm, &setup.vault_a);
+
+    let msg = Message::new(&[transfer_ixn, swap], Some(&setup.user));
+    setup.svm.send_transaction(Transaction::new_unsigned(msg)).unwrap();
+
+    let after_out = get_token_balance(&setup.svm, &setup.user_ata_b);
+    let vault_a_after = get_token_balance(&setup.svm, &setup.vault_a);
+
+    let fee_amount = amount_in as u128 * fee_bps as u128 / 10_000;
+    let amount_in_after_fee = amount_in as u128 - fee_amount;
+    let expected_out = (amount_in_after_fee * usdc_atoms_per_sol as u128 / 1_000_000_000) as u64;
+    assert_eq!(after_out - before_out, expected_out);
+
+    // the fee stays in the input vault rather than being paid out or burned
+    assert_eq!(vault_a_after - vault_a_before, amount_in);
+}
+
+#[test]
+fn swap_rejects_when_below_minimum_amount_out() {
+    let usdc_atoms_per_sol: u64 = 1_000 * 1_000_000; // $1000 per SOL
+    let setup = setup_pool(introspectardio::CURVE_FIXED, 0, usdc_atoms_per_sol);
+
+    let amount_in: u64 = LAMPORTS_PER_SOL; // 1 SOL
+    let expected_out = amount_in * usdc_atoms_per_sol / LAMPORTS_PER_SOL;
+
+    let transfer_ixn =
+        spl_token_transfer_instruction(&setup.user_ata_a, &setup.vault_a, &setup.user, amount_in);
+
+    // disc 1 (swap) followed by an 8-byte minimum_amount_out one atom above
+    // what the fixed-rate curve will actually pay out
+    let mut data = vec![1];
+    data.extend_from_slice(&(expected_out + 1).to_le_bytes());
+    let swap = swap_ixn(&setup, setup.user_ata_b, data);
+
+    let mut svm = setup.svm;
+    let msg = Message::new(&[transfer_ixn, swap], Some(&setup.user));
+    assert!(svm.send_transaction(Transaction::new_unsigned(msg)).is_err());
+}
+
+
+#[test]
+fn same_tx_bidirectional_swaps_succeed() {
+    let usdc_atoms_per_sol: u64 = 1_000 * 1_000_000; // $1000 per SOL
+    let mut setup = setup_pool(introspectardio::CURVE_FIXED, 0, usdc_atoms_per_sol);
+
+    // fund both vaults directly to stand in for an initial liquidity position
+    let seed_a = spl_token_transfer_instruction(
+        &setup.user_ata_a,
+        &setup.vault_a,
+        &setup.user,
+        10 * LAMPORTS_PER_SOL,
+    );
+    let seed_b = spl_token_transfer_instruction(
+        &setup.user_ata_b,
+        &setup.vault_b,
+        &setup.user,
+        10_000 * 1_000_000,
+    );
+    setup
+        .svm
+        .send_transaction(Transaction::new_unsigned(Message::new(
+            &[seed_a, seed_b],
+            Some(&setup.user),
+        )))
+        .unwrap();
+
+    // one transaction, two swaps in opposite directions, each paired with its
+    // own immediately-preceding transfer: A->B first, then B->A.
+    let amount_a_in: u64 = LAMPORTS_PER_SOL; // 1 SOL
+    let transfer_a = spl_token_transfer_instruction(
+        &setup.user_ata_a,
+        &setup.vault_a,
+        &setup.user,
+        amount_a_in,
+    );
+    let swap_a_to_b = swap_ixn(&setup, setup.user_ata_b, vec![1]);
+
+    let amount_b_in: u64 = 500 * 1_000_000; // 500 USDC
+    let transfer_b = spl_token_transfer_instruction(
+        &setup.user_ata_b,
+        &setup.vault_b,
+        &setup.user,
+        amount_b_in,
+    );
+    let swap_b_to_a = swap_ixn(&setup, setup.user_ata_a, vec![1]);
+
+    let out_b_before = get_token_balance(&setup.svm, &setup.user_ata_b);
+    let out_a_before = get_token_balance(&setup.svm, &setup.user_ata_a);
+
+    let msg = Message::new(
+        &[transfer_a, swap_a_to_b, transfer_b, swap_b_to_a],
+        Some(&setup.user),
+    );
+    setup
+        .svm
+        .send_transaction(Transaction::new_unsigned(msg))
+        .unwrap();
+
+    let out_b_after = get_token_balance(&setup.svm, &setup.user_ata_b);
+    let out_a_after = get_token_balance(&setup.svm, &setup.user_ata_a);
+
+    // each ATA both pays into one swap's transfer and receives the other
+    // swap's payout, so net change is payout minus what it funded (signed,
+    // since either side of that can come out negative)
+    let expected_a_to_b = amount_a_in * usdc_atoms_per_sol / LAMPORTS_PER_SOL;
+    let expected_b_to_a = amount_b_in * LAMPORTS_PER_SOL / usdc_atoms_per_sol;
+    assert_eq!(
+        out_b_after as i128 - out_b_before as i128,
+        expected_a_to_b as i128 - amount_b_in as i128
+    );
+    assert_eq!(
+        out_a_after as i128 - out_a_before as i128,
+        expected_b_to_a as i128 - amount_a_in as i128
+    );
 }